@@ -44,13 +44,19 @@ impl<T: Into<ErrorKind>> From<T> for Error {
             | ErrorKind::ParseArg { .. }
             | ErrorKind::CommaInImageTag { .. }
             | ErrorKind::UserNotInVoiceChanel { .. }
+            | ErrorKind::RateLimited { .. }
+            | ErrorKind::StreamNotStartedYet { .. }
+            | ErrorKind::NotFound { .. }
             | ErrorKind::NoActiveTrack { .. } => true,
             ErrorKind::JoinVoiceChannel { .. }
             | ErrorKind::AudioStart { .. }
             | ErrorKind::UnknownDiscord { .. }
+            | ErrorKind::HttpClientInit { .. }
             | ErrorKind::SendRequest { .. }
             | ErrorKind::GetRequest { .. }
             | ErrorKind::UnexpectedJsonShape { .. }
+            | ErrorKind::NoHealthyInstance { .. }
+            | ErrorKind::SubprocessFailed { .. }
             | ErrorKind::YtVidNotFound { .. }
             | ErrorKind::YtInferVideoId { .. } => false,
         };
@@ -132,6 +138,9 @@ pub enum ErrorKind {
     #[error("Unknown discord error: {0}")]
     UnknownDiscord(#[from] serenity::Error),
 
+    #[error("Failed to initialize the HTTP client")]
+    HttpClientInit(reqwest::Error),
+
     #[error("Failed to send an http request")]
     SendRequest(reqwest::Error),
 
@@ -141,14 +150,43 @@ pub enum ErrorKind {
         body: String,
     },
 
+    #[error(
+        "The request was rate limited{}",
+        .retry_after
+            .map(|it| format!(", try again in {} seconds", it.as_secs()))
+            .unwrap_or_default()
+    )]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+    },
+
+    #[error("The requested resource was not found: {url}")]
+    NotFound { url: Url },
+
     #[error("YouTube has returned an unexpected response JSON obejct")]
     UnexpectedJsonShape(reqwest::Error),
 
+    #[error("No Invidious instance is available to serve the request")]
+    NoHealthyInstance,
+
     #[error("Failed to find youtube video for \"{0}\" query.)")]
     YtVidNotFound(String),
 
     #[error("Could not infer YouTube video id from the url `{0}`")]
     YtInferVideoId(Url),
+
+    #[error(
+        "This stream has not started yet{}",
+        .starts_at
+            .map(|it| format!(", it is scheduled to begin at {}", it.to_rfc2822()))
+            .unwrap_or_default()
+    )]
+    StreamNotStartedYet {
+        starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    },
+
+    #[error("A subprocess exited with a non-zero status:\n{stderr}")]
+    SubprocessFailed { stderr: String },
 }
 
 impl ErrorKind {
@@ -163,9 +201,16 @@ impl ErrorKind {
             | ErrorKind::TrackIndexOutOfBounds { .. } => "Invalid argument error",
             ErrorKind::UserNotInVoiceChanel => "Not in a voice channel error",
             ErrorKind::JoinVoiceChannel(_) => "Permissions error",
-            ErrorKind::AudioStart(_) | ErrorKind::UnknownDiscord(_) => "Internal error",
+            ErrorKind::AudioStart(_)
+            | ErrorKind::UnknownDiscord(_)
+            | ErrorKind::SubprocessFailed { .. } => "Internal error",
+            ErrorKind::StreamNotStartedYet { .. } => "Stream not started yet",
+            ErrorKind::HttpClientInit(_) => "Internal error",
             ErrorKind::SendRequest(_) => "Send request error",
             ErrorKind::GetRequest { .. } | ErrorKind::UnexpectedJsonShape(_) => "HTTP error",
+            ErrorKind::NoHealthyInstance => "Internal error",
+            ErrorKind::NotFound { .. } => "Not found",
+            ErrorKind::RateLimited { .. } => "Rate limited",
             ErrorKind::YtVidNotFound(_) => "YouTube error",
             ErrorKind::YtInferVideoId { .. } => "Bad YouTube URL",
         }
@@ -2,7 +2,7 @@
 
 use std::time;
 
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize};
 use serenity::async_trait;
 use url::Url;
 
@@ -26,6 +26,69 @@ macro_rules! _def_url_base {
 
 pub(crate) use {_def_url_base as def_url_base, _regex as regex};
 
+/// Tunables for the automatic retry-with-backoff behaviour of
+/// [`ReqwestClientExt::send_get_json_request`]. The defaults are sensible for
+/// the flaky third-party endpoints this bot talks to (YouTube et al.).
+#[derive(Debug, Clone)]
+pub(crate) struct RetryConfig {
+    /// How many times to retry *after* the initial attempt.
+    pub(crate) max_retries: u32,
+    /// Backoff interval for the first retry. Grows as `base * 2^attempt`.
+    pub(crate) base: time::Duration,
+    /// Upper bound for a single backoff interval (before jitter).
+    pub(crate) max: time::Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base: time::Duration::from_millis(250),
+            max: time::Duration::from_secs(30),
+        }
+    }
+}
+
+/// The exponential backoff interval for the given (zero-based) retry `attempt`,
+/// `base * 2^attempt` capped at [`RetryConfig::max`], before jitter is added.
+/// This is the value policy decisions (e.g. "is this delay short enough to
+/// retry inline") should key off of, since it is deterministic.
+fn capped_backoff(config: &RetryConfig, attempt: u32) -> time::Duration {
+    let exp = config.base.saturating_mul(2u32.saturating_pow(attempt));
+    exp.min(config.max)
+}
+
+/// Compute the exponential backoff delay for the given (zero-based) retry
+/// `attempt`: [`capped_backoff`] plus a random fraction of the interval on top
+/// to avoid a thundering herd of synchronized retries.
+pub(crate) fn backoff_delay(config: &RetryConfig, attempt: u32) -> time::Duration {
+    let capped = capped_backoff(config, attempt);
+    let jitter = capped.mul_f64(rand::random::<f64>());
+    capped + jitter
+}
+
+/// Whether a failed `send()` is worth retrying. Connection resets and timeouts
+/// are transient; a malformed request is not.
+fn is_retryable_send_error(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect() || err.is_request()
+}
+
+/// Parse the `Retry-After` response header into a wait duration. The header
+/// comes in two flavours (RFC 7231): a delta in seconds (`Retry-After: 120`)
+/// or an absolute HTTP-date (`Retry-After: Wed, 21 Oct 2015 07:28:00 GMT`).
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<time::Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(time::Duration::from_secs(secs));
+    }
+
+    let deadline = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (deadline.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
 #[async_trait]
 pub(crate) trait ReqwestClientExt {
     async fn send_get_json_request<T: DeserializeOwned>(
@@ -33,6 +96,13 @@ pub(crate) trait ReqwestClientExt {
         url: Url,
         query: &[(&str, &str)],
     ) -> crate::Result<T>;
+
+    async fn send_get_json_request_with<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query: &[(&str, &str)],
+        retry: &RetryConfig,
+    ) -> crate::Result<T>;
 }
 
 #[async_trait]
@@ -42,35 +112,458 @@ impl ReqwestClientExt for reqwest::Client {
         url: Url,
         query: &[(&str, &str)],
     ) -> crate::Result<T> {
-        let res = self
-            .get(url)
-            .query(query)
-            .header("User-Agent", "Veebot")
-            .send()
+        self.send_get_json_request_with(url, query, &RetryConfig::default())
             .await
-            .map_err(|err| crate::err!(SendRequest(err)))?;
+    }
 
-        let status = res.status();
+    async fn send_get_json_request_with<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query: &[(&str, &str)],
+        retry: &RetryConfig,
+    ) -> crate::Result<T> {
+        let mut attempt = 0;
 
-        if status.is_client_error() || status.is_server_error() {
-            let body = match res.text().await {
-                Ok(it) => it,
-                Err(err) => format!("Could not collect the GET request body: {}", err),
+        loop {
+            let res = match self
+                .get(url.clone())
+                .query(query)
+                .header("User-Agent", "Veebot")
+                .send()
+                .await
+            {
+                Ok(res) => res,
+                Err(err) => {
+                    if attempt < retry.max_retries && is_retryable_send_error(&err) {
+                        tokio::time::sleep(backoff_delay(retry, attempt)).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    return Err(crate::err!(SendRequest(err)));
+                }
             };
 
-            return Err(crate::err!(GetRequest { status, body }));
-        }
+            let status = res.status();
 
-        res.json()
-            .await
-            .map_err(|err| crate::err!(UnexpectedJsonShape(err)))
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = parse_retry_after(res.headers());
+
+                // Sleep-and-retry transparently when the wait is short, else
+                // bubble up a structured error so the user can be told when to
+                // try again. The decision uses the deterministic (pre-jitter)
+                // delay so it does not hinge on `rand`.
+                let decision_wait =
+                    retry_after.unwrap_or_else(|| capped_backoff(retry, attempt));
+
+                if attempt < retry.max_retries && decision_wait <= retry.max {
+                    let wait = retry_after.unwrap_or_else(|| backoff_delay(retry, attempt));
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                    continue;
+                }
+
+                return Err(crate::err!(RateLimited { retry_after }));
+            }
+
+            if status.is_server_error() && attempt < retry.max_retries {
+                tokio::time::sleep(backoff_delay(retry, attempt)).await;
+                attempt += 1;
+                continue;
+            }
+
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(crate::err!(NotFound { url }));
+            }
+
+            if status.is_client_error() || status.is_server_error() {
+                let body = match res.text().await {
+                    Ok(it) => it,
+                    Err(err) => format!("Could not collect the GET request body: {}", err),
+                };
+
+                return Err(crate::err!(GetRequest { status, body }));
+            }
+
+            return res
+                .json()
+                .await
+                .map_err(|err| crate::err!(UnexpectedJsonShape(err)));
+        }
     }
 }
 
-pub(crate) fn create_http_client() -> reqwest::Client {
-    reqwest::Client::builder()
-        .timeout(time::Duration::from_secs(30))
-        .connect_timeout(time::Duration::from_secs(30))
+/// Build the shared outbound HTTP client, selecting the TLS backend at compile
+/// time via the `*-tls` Cargo features and reporting a misconfigured backend as
+/// `HttpClientInit` instead of panicking at startup.
+pub(crate) fn create_http_client(
+    request_timeout: time::Duration,
+    connect_timeout: time::Duration,
+) -> crate::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder()
+        .timeout(request_timeout)
+        .connect_timeout(connect_timeout);
+
+    #[cfg(feature = "native-tls")]
+    let builder = builder.use_native_tls();
+
+    #[cfg(feature = "rustls-tls-webpki-roots")]
+    let builder = builder.use_rustls_tls().tls_built_in_webpki_certs(true);
+
+    #[cfg(feature = "rustls-tls-native-roots")]
+    let builder = builder.use_rustls_tls().tls_built_in_native_certs(true);
+
+    builder
         .build()
-        .expect("rustls backend initialization should never error out")
+        .map_err(|err| crate::err!(HttpClientInit(err)))
+}
+
+/// Number of consecutive failures after which an instance is benched.
+const INSTANCE_FAILURE_THRESHOLD: u32 = 3;
+/// How long a benched instance is skipped before being tried again.
+const INSTANCE_COOLDOWN: time::Duration = time::Duration::from_secs(300);
+
+/// Health bookkeeping for a single mirror base in an [`InvidiousPool`].
+#[derive(Debug)]
+struct Instance {
+    base: Url,
+    consecutive_failures: u32,
+    skip_until: Option<tokio::time::Instant>,
+}
+
+/// Ordered pool of interchangeable Invidious mirror bases, used as a fallback
+/// when YouTube's own endpoints block or throttle the bot.
+#[derive(Debug)]
+pub(crate) struct InvidiousPool {
+    client: reqwest::Client,
+    retry: RetryConfig,
+    instances: tokio::sync::Mutex<Vec<Instance>>,
+}
+
+impl InvidiousPool {
+    /// Create a pool over the given ordered list of mirror base URLs. Bases are
+    /// tried front-to-back, so list the most reliable instances first.
+    pub(crate) fn new(client: reqwest::Client, bases: impl IntoIterator<Item = Url>) -> Self {
+        let instances = bases
+            .into_iter()
+            .map(|base| Instance {
+                base,
+                consecutive_failures: 0,
+                skip_until: None,
+            })
+            .collect();
+
+        Self {
+            client,
+            retry: RetryConfig::default(),
+            instances: tokio::sync::Mutex::new(instances),
+        }
+    }
+
+    /// Replay a GET request for the given path `segments` and `query` against
+    /// each healthy instance in turn, returning the first successful JSON body.
+    /// Transient errors (see [`should_failover`]) rotate to the next instance and
+    /// the last one is surfaced once every instance is exhausted; any other
+    /// error is returned immediately without trying the remaining instances.
+    pub(crate) async fn send_get_json_request<T: DeserializeOwned>(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+    ) -> crate::Result<T> {
+        let now = tokio::time::Instant::now();
+        let mut last_err = None;
+
+        let len = self.instances.lock().await.len();
+
+        if len == 0 {
+            return Err(crate::err!(NoHealthyInstance));
+        }
+
+        // First pass respects cooldowns; if that would skip every instance
+        // (all of them benched), fall back to trying them anyway rather than
+        // reporting `NoHealthyInstance` without making a single request.
+        for respect_cooldown in [true, false] {
+            for idx in 0..len {
+                let url = {
+                    let instances = self.instances.lock().await;
+                    let instance = &instances[idx];
+                    if respect_cooldown && instance.skip_until.map_or(false, |until| until > now) {
+                        continue;
+                    }
+                    let mut url = instance.base.clone();
+                    url.path_segments_mut()
+                        .expect("invidious base url must be a base")
+                        .extend(segments);
+                    url
+                };
+
+                match self
+                    .client
+                    .send_get_json_request_with(url, query, &self.retry)
+                    .await
+                {
+                    Ok(body) => {
+                        self.record_success(idx).await;
+                        return Ok(body);
+                    }
+                    Err(err) if should_failover(&err) => {
+                        self.record_failure(idx).await;
+                        last_err = Some(err);
+                    }
+                    // A deterministic error (e.g. a 4xx other than 429) will
+                    // look the same on every mirror, so there is no point
+                    // rotating.
+                    Err(err) => return Err(err),
+                }
+            }
+
+            // The cooldown-respecting pass made at least one request, so there
+            // is a real error to surface instead of retrying without cooldowns.
+            if last_err.is_some() {
+                break;
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| crate::err!(NoHealthyInstance)))
+    }
+
+    async fn record_success(&self, idx: usize) {
+        let mut instances = self.instances.lock().await;
+        let instance = &mut instances[idx];
+        instance.consecutive_failures = 0;
+        instance.skip_until = None;
+    }
+
+    async fn record_failure(&self, idx: usize) {
+        let mut instances = self.instances.lock().await;
+        let instance = &mut instances[idx];
+        instance.consecutive_failures += 1;
+        if instance.consecutive_failures >= INSTANCE_FAILURE_THRESHOLD {
+            instance.skip_until = Some(tokio::time::Instant::now() + INSTANCE_COOLDOWN);
+        }
+    }
+}
+
+/// A playable stream resolved out of band by [`extract_with_yt_dlp`].
+#[derive(Debug)]
+pub(crate) struct ExtractedStream {
+    /// Direct URL of the media stream yt-dlp selected.
+    pub(crate) url: Url,
+}
+
+/// Subset of the `yt-dlp --dump-json` object we care about.
+#[derive(Debug, Deserialize)]
+struct YtDlpInfo {
+    /// Pre-selected best stream url (present once yt-dlp picks a format).
+    url: Option<String>,
+    /// Per-format entries, used as a fallback when `url` is absent.
+    #[serde(default)]
+    formats: Vec<YtDlpFormat>,
+    /// Unix timestamp at which an upcoming live event / premiere begins.
+    #[serde(default)]
+    release_timestamp: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YtDlpFormat {
+    url: Option<String>,
+}
+
+/// Resolve a playable stream by shelling out to `yt-dlp` when the native HTTP
+/// JSON path fails, reporting an unstarted live event or premiere as
+/// `StreamNotStartedYet` instead of a bogus "video not found".
+pub(crate) async fn extract_with_yt_dlp(video_url: &Url) -> crate::Result<ExtractedStream> {
+    let output = tokio::process::Command::new("yt-dlp")
+        .args(["--skip-download", "--dump-json", "--quiet"])
+        .arg(video_url.as_str())
+        .output()
+        .await
+        .map_err(|err| {
+            crate::err!(SubprocessFailed {
+                stderr: format!("failed to spawn yt-dlp: {}", err),
+            })
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        // An upcoming live event / premiere fails to extract, but yt-dlp still
+        // reports when it is scheduled to start.
+        if let Some(starts_at) = parse_scheduled_start_time(&stderr) {
+            return Err(crate::err!(StreamNotStartedYet {
+                starts_at: Some(starts_at)
+            }));
+        }
+
+        return Err(crate::err!(SubprocessFailed { stderr }));
+    }
+
+    let info: YtDlpInfo = serde_json::from_slice(&output.stdout).map_err(|err| {
+        crate::err!(SubprocessFailed {
+            stderr: format!("could not parse yt-dlp output as JSON: {}", err),
+        })
+    })?;
+
+    // A video that is still upcoming parses fine but carries no playable url.
+    let raw_url = info
+        .url
+        .or_else(|| info.formats.into_iter().rev().find_map(|it| it.url));
+
+    let raw_url = match raw_url {
+        Some(it) => it,
+        // No playable url: only call it an unstarted stream when yt-dlp actually
+        // reported a scheduled start, otherwise it is a plain extraction failure
+        // (DRM, region lock, fragmented-only formats, ...).
+        None => match info.release_timestamp {
+            Some(ts) => {
+                let starts_at = chrono::DateTime::from_timestamp(ts, 0);
+                return Err(crate::err!(StreamNotStartedYet { starts_at }));
+            }
+            None => {
+                return Err(crate::err!(SubprocessFailed {
+                    stderr: "yt-dlp returned no playable stream url".to_owned(),
+                }));
+            }
+        },
+    };
+
+    let url = raw_url
+        .parse()
+        .map_err(|err| crate::err!(SubprocessFailed {
+            stderr: format!("yt-dlp returned a malformed stream url: {}", err),
+        }))?;
+
+    Ok(ExtractedStream { url })
+}
+
+/// Pull a `scheduledStartTime` (unix seconds) out of a yt-dlp error blob and
+/// turn it into a UTC timestamp, if present.
+fn parse_scheduled_start_time(stderr: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let captures = regex!(r#"scheduledStartTime"?\s*[:=]\s*"?(\d+)"#).captures(stderr)?;
+    let ts: i64 = captures.get(1)?.as_str().parse().ok()?;
+    chrono::DateTime::from_timestamp(ts, 0)
+}
+
+/// Whether the given error means the current instance is unhealthy and the
+/// request should be replayed against the next one.
+fn should_failover(err: &crate::Error) -> bool {
+    use crate::error::ErrorKind;
+    match &err.kind {
+        ErrorKind::SendRequest(_)
+        | ErrorKind::RateLimited { .. }
+        | ErrorKind::UnexpectedJsonShape(_) => true,
+        ErrorKind::GetRequest { status, .. } => status.is_server_error(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> RetryConfig {
+        RetryConfig {
+            max_retries: 4,
+            base: time::Duration::from_millis(100),
+            max: time::Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn capped_backoff_grows_exponentially_then_caps() {
+        let cfg = cfg();
+        assert_eq!(capped_backoff(&cfg, 0), time::Duration::from_millis(100));
+        assert_eq!(capped_backoff(&cfg, 1), time::Duration::from_millis(200));
+        assert_eq!(capped_backoff(&cfg, 2), time::Duration::from_millis(400));
+        // 100ms * 2^4 = 1600ms, capped at the configured 1s maximum.
+        assert_eq!(capped_backoff(&cfg, 4), time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_delay_stays_between_capped_and_double() {
+        let cfg = cfg();
+        for attempt in 0..6 {
+            let capped = capped_backoff(&cfg, attempt);
+            let delay = backoff_delay(&cfg, attempt);
+            assert!(delay >= capped, "jitter must not shrink the base interval");
+            assert!(delay <= capped * 2, "jitter must stay within one interval");
+        }
+    }
+
+    #[test]
+    fn parse_retry_after_reads_delta_seconds() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(
+            parse_retry_after(&headers),
+            Some(time::Duration::from_secs(120))
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_is_none_for_missing_or_garbage() {
+        let empty = reqwest::header::HeaderMap::new();
+        assert_eq!(parse_retry_after(&empty), None);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn should_failover_rotates_on_transient_errors_only() {
+        use crate::error::ErrorKind;
+
+        let server_err = crate::Error::from(ErrorKind::GetRequest {
+            status: reqwest::StatusCode::BAD_GATEWAY,
+            body: String::new(),
+        });
+        assert!(should_failover(&server_err));
+        assert!(should_failover(&crate::Error::from(ErrorKind::RateLimited {
+            retry_after: None
+        })));
+
+        let client_err = crate::Error::from(ErrorKind::GetRequest {
+            status: reqwest::StatusCode::BAD_REQUEST,
+            body: String::new(),
+        });
+        assert!(!should_failover(&client_err));
+        assert!(!should_failover(&crate::Error::from(ErrorKind::NotFound {
+            url: Url::parse("https://example.test/").unwrap(),
+        })));
+    }
+
+    fn test_pool(bases: Vec<Url>) -> InvidiousPool {
+        let client = create_http_client(
+            time::Duration::from_secs(5),
+            time::Duration::from_secs(5),
+        )
+        .unwrap();
+        InvidiousPool::new(client, bases)
+    }
+
+    #[tokio::test]
+    async fn instance_is_benched_after_threshold_and_reset_on_success() {
+        let pool = test_pool(vec![Url::parse("https://a.test/").unwrap()]);
+
+        for _ in 0..INSTANCE_FAILURE_THRESHOLD {
+            pool.record_failure(0).await;
+        }
+        assert!(pool.instances.lock().await[0].skip_until.is_some());
+
+        pool.record_success(0).await;
+        let instances = pool.instances.lock().await;
+        assert!(instances[0].skip_until.is_none());
+        assert_eq!(instances[0].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn empty_pool_reports_no_healthy_instance() {
+        let pool = test_pool(Vec::new());
+        let res: crate::Result<serde_json::Value> =
+            pool.send_get_json_request(&["anything"], &[]).await;
+        assert!(matches!(
+            res.unwrap_err().kind,
+            crate::error::ErrorKind::NoHealthyInstance
+        ));
+    }
 }